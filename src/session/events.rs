@@ -0,0 +1,140 @@
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use once_cell::sync::OnceCell;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use super::Session;
+
+/// A single event in transit to a sink, along with the context needed to
+/// interpret it.
+///
+/// Carries the event's original serialized bytes alongside its typed
+/// value, so a hop through the store-and-forward queue or a fan-out to
+/// another sink round-trips fields `T::Event` doesn't model rather than
+/// silently dropping them. The typed value is deserialized lazily (and
+/// cached) on first access via [`deserialize`](Self::deserialize).
+pub struct EventSinkCtx<T: Session> {
+    raw: Arc<[u8]>,
+    typed: OnceCell<T::Event>,
+    seq: u64,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T: Session> EventSinkCtx<T> {
+    /// Build a context directly from an event's serialized bytes, e.g.
+    /// straight off the wire. Prefer this over [`new`](Self::new) on any
+    /// path that already has the raw bytes, so fields `T::Event` doesn't
+    /// model survive the hop untouched.
+    pub fn from_raw(raw: impl Into<Arc<[u8]>>) -> Self {
+        EventSinkCtx {
+            raw: raw.into(),
+            typed: OnceCell::new(),
+            seq: 0,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Build a context from an already-deserialized event, serializing it
+    /// once up front so [`raw`](Self::raw) has something to return. Fails
+    /// if `serde_json` can't serialize `event` (e.g. a map whose keys don't
+    /// serialize as strings), which is a property of the value, not a bug,
+    /// so it's surfaced as an error rather than a panic. Deserializing the
+    /// bytes back is not checked here.
+    pub fn new(event: T::Event) -> anyhow::Result<Self>
+    where
+        T::Event: Serialize,
+    {
+        let raw: Arc<[u8]> = serde_json::to_vec(&event)?.into();
+        let typed = OnceCell::new();
+        let _ = typed.set(event);
+        Ok(EventSinkCtx {
+            raw,
+            typed,
+            seq: 0,
+            _marker: PhantomData,
+        })
+    }
+
+    /// The event's original serialized bytes.
+    pub fn raw(&self) -> &[u8] {
+        &self.raw
+    }
+
+    /// The typed event, deserializing (and caching the result) from
+    /// [`raw`](Self::raw) on first access.
+    pub fn deserialize(&self) -> anyhow::Result<&T::Event>
+    where
+        T::Event: DeserializeOwned,
+    {
+        self.typed
+            .get_or_try_init(|| serde_json::from_slice(&self.raw).map_err(anyhow::Error::from))
+    }
+
+    /// Tag this event with a producer-assigned sequence number, used by
+    /// [`super::reorder::ReorderBuffer`] to reassemble a stream of events
+    /// from one or more producers in order.
+    pub fn with_seq(mut self, seq: u64) -> Self {
+        self.seq = seq;
+        self
+    }
+
+    pub fn seq(&self) -> u64 {
+        self.seq
+    }
+}
+
+impl<T: Session> Clone for EventSinkCtx<T> {
+    fn clone(&self) -> Self {
+        EventSinkCtx {
+            raw: self.raw.clone(),
+            typed: self.typed.clone(),
+            seq: self.seq,
+            _marker: PhantomData,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct EventsTestSession;
+
+    impl Session for EventsTestSession {
+        type Key = String;
+        type Event = u64;
+    }
+
+    #[test]
+    fn new_round_trips_through_raw_and_deserialize() {
+        let ctx = EventSinkCtx::<EventsTestSession>::new(42).unwrap();
+        assert_eq!(ctx.raw(), b"42");
+        assert_eq!(*ctx.deserialize().unwrap(), 42);
+    }
+
+    #[test]
+    fn from_raw_round_trips_through_deserialize() {
+        let ctx = EventSinkCtx::<EventsTestSession>::from_raw(b"7".to_vec());
+        assert_eq!(ctx.raw(), b"7");
+        assert_eq!(*ctx.deserialize().unwrap(), 7);
+    }
+
+    #[test]
+    fn new_surfaces_serialization_failure_instead_of_panicking() {
+        // A map keyed by a tuple is a perfectly valid Rust value, but
+        // serde_json can only use it as a JSON object key if it serializes
+        // to a string, which a tuple never does.
+        let mut event = std::collections::BTreeMap::new();
+        event.insert((1u64, 2u64), "value");
+        assert!(EventSinkCtx::<MapEventSession>::new(event).is_err());
+    }
+
+    struct MapEventSession;
+
+    impl Session for MapEventSession {
+        type Key = String;
+        type Event = std::collections::BTreeMap<(u64, u64), &'static str>;
+    }
+}
@@ -0,0 +1,154 @@
+use std::any::{Any, TypeId};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use once_cell::sync::Lazy;
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+
+use super::events::EventSinkCtx;
+use super::map::{
+    existing_entries, get_or_create_event_sink_map, EventSinkEntry, SessionEnded, SessionKindMap,
+};
+use super::Session;
+
+/// Push-style consumer of events for session kind `T`, registered with
+/// [`register_handler`]. An implementor is driven by a background task per
+/// live device sink rather than polling [`EventSinkEntry::get`] itself.
+#[async_trait]
+pub trait EventHandler<T: Session>: Send + Sync + 'static {
+    /// Called for every event delivered to `key`.
+    async fn on_event(&self, key: &T::Key, ctx: EventSinkCtx<T>);
+
+    /// Called once `key`'s sink reports a clean close. Not called on
+    /// liveness eviction; see [`super::map::EventSinkEntry::is_alive`].
+    async fn on_session_close(&self, key: &T::Key) {
+        let _ = key;
+    }
+}
+
+type HandlerList<T> = RwLock<Vec<Arc<dyn EventHandler<T>>>>;
+
+static HANDLER_MAP: Lazy<SessionKindMap> = Lazy::new(Default::default);
+
+async fn handlers_for<T: Session>() -> Arc<HandlerList<T>> {
+    {
+        let locked = HANDLER_MAP.read().await;
+        if let Some(entry) = locked.get(&TypeId::of::<T>()) {
+            return entry.clone().downcast::<HandlerList<T>>().unwrap();
+        }
+    }
+
+    let mut locked = HANDLER_MAP.write().await;
+    locked
+        .entry(TypeId::of::<T>())
+        .or_insert_with(|| Arc::new(HandlerList::<T>::default()) as Arc<dyn Any + Send + Sync>)
+        .clone()
+        .downcast::<HandlerList<T>>()
+        .unwrap()
+}
+
+/// Register `handler` to receive every event published for session kind
+/// `T`, past and future. A driver task is spawned for each device sink
+/// already registered, and each registration that adds a new device
+/// afterwards spawns one for that device.
+pub async fn register_handler<T: Session>(handler: Arc<dyn EventHandler<T>>) {
+    let list = handlers_for::<T>().await;
+    list.write().await.push(handler.clone());
+
+    let map = get_or_create_event_sink_map::<T>().await;
+    for (key, entry) in existing_entries(&map).await {
+        spawn_driver(key, entry, handler.clone());
+    }
+}
+
+fn spawn_driver<T: Session>(
+    key: T::Key,
+    entry: EventSinkEntry<T>,
+    handler: Arc<dyn EventHandler<T>>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            match entry.get().await {
+                Ok(ctx) => handler.on_event(&key, ctx).await,
+                Err(err) => {
+                    // `on_session_close` is documented as not firing on
+                    // liveness eviction, so only call it for a clean close.
+                    if err.downcast_ref::<SessionEnded>() != Some(&SessionEnded::Evicted) {
+                        handler.on_session_close(&key).await;
+                    }
+                    break;
+                }
+            }
+        }
+    })
+}
+
+/// Spawn a driver task per handler registered for `T`, bridging the newly
+/// registered `entry` to each. Called on every registration that adds a new
+/// device under a key (via [`super::map::register_sink`],
+/// [`super::map::register_device`] and friends), not only the first.
+pub(super) async fn notify_handlers_of_new_entry<T: Session>(
+    key: T::Key,
+    entry: EventSinkEntry<T>,
+) {
+    let list = handlers_for::<T>().await;
+    let handlers: Vec<_> = list.read().await.iter().cloned().collect();
+    for handler in handlers {
+        spawn_driver(key.clone(), entry.clone(), handler);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    struct DriverTestSession;
+
+    impl Session for DriverTestSession {
+        type Key = String;
+        type Event = u64;
+    }
+
+    #[derive(Default)]
+    struct RecordingHandler {
+        closed: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl EventHandler<DriverTestSession> for RecordingHandler {
+        async fn on_event(&self, _key: &String, _ctx: EventSinkCtx<DriverTestSession>) {}
+
+        async fn on_session_close(&self, _key: &String) {
+            self.closed.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[tokio::test]
+    async fn on_session_close_fires_on_clean_close() {
+        let handler = Arc::new(RecordingHandler::default());
+        let entry = EventSinkEntry::<DriverTestSession>::default();
+        entry.close();
+
+        spawn_driver("closed-key".to_string(), entry, handler.clone())
+            .await
+            .unwrap();
+
+        assert_eq!(handler.closed.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn on_session_close_does_not_fire_on_eviction() {
+        let handler = Arc::new(RecordingHandler::default());
+        let entry = EventSinkEntry::<DriverTestSession>::default();
+        entry.evict();
+
+        spawn_driver("evicted-key".to_string(), entry, handler.clone())
+            .await
+            .unwrap();
+
+        assert_eq!(handler.closed.load(Ordering::SeqCst), 0);
+    }
+}
@@ -1,53 +1,214 @@
 use std::any::{Any, TypeId};
 use std::collections::hash_map::{Entry, HashMap};
-use std::sync::Arc;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::{Duration, Instant};
 
 use anyhow::anyhow;
 use futures::never::Never;
 use futures::TryFutureExt;
 use once_cell::sync::Lazy;
-use tokio::sync::{watch, RwLock};
+use tokio::sync::{watch, Mutex as AsyncMutex, RwLock};
 
 use super::events::EventSinkCtx;
+use super::handler::notify_handlers_of_new_entry;
+use super::store::get_or_create_session_store;
 use super::Session;
 
 pub type SessionKindMap = RwLock<HashMap<TypeId, Arc<dyn Any + 'static + Sync + Send>>>;
 
+/// Why [`EventSinkEntry::get`] stopped yielding events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionEnded {
+    /// The sink was closed deliberately, e.g. via [`EventSinkEntry::close`].
+    Closed,
+    /// The sink was dropped from its [`EventSinkMap`] by the liveness
+    /// sweeper after exceeding its `liveness_timeout` with no activity.
+    Evicted,
+}
+
+impl std::fmt::Display for SessionEnded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SessionEnded::Closed => write!(f, "session closed"),
+            SessionEnded::Evicted => write!(f, "session evicted after liveness timeout"),
+        }
+    }
+}
+
+impl std::error::Error for SessionEnded {}
+
+enum SinkSignal<T: Session> {
+    Pending,
+    Event(EventSinkCtx<T>),
+    Ended(SessionEnded),
+}
+
+impl<T: Session> Clone for SinkSignal<T> {
+    fn clone(&self) -> Self {
+        match self {
+            SinkSignal::Pending => SinkSignal::Pending,
+            SinkSignal::Event(ctx) => SinkSignal::Event(ctx.clone()),
+            SinkSignal::Ended(reason) => SinkSignal::Ended(*reason),
+        }
+    }
+}
+
 pub struct EventSinkEntry<T: Session> {
-    rx: watch::Receiver<Option<EventSinkCtx<T>>>,
+    tx: watch::Sender<SinkSignal<T>>,
+    rx: watch::Receiver<SinkSignal<T>>,
+    // Events replayed from a `SessionStore` backlog on registration. A
+    // `watch` channel only ever holds its latest value, so a backlog of
+    // more than one event can't be fanned through `tx` without silently
+    // overwriting all but the last — these are queued here instead and
+    // drained in order ahead of anything arriving live on `rx`.
+    backlog: Arc<AsyncMutex<VecDeque<EventSinkCtx<T>>>>,
+    last_seen: Arc<StdMutex<Instant>>,
+    liveness_timeout: Arc<StdMutex<Option<Duration>>>,
 }
 
 impl<T: Session> Clone for EventSinkEntry<T> {
     fn clone(&self) -> Self {
         EventSinkEntry {
+            tx: self.tx.clone(),
             rx: self.rx.clone(),
+            backlog: self.backlog.clone(),
+            last_seen: self.last_seen.clone(),
+            liveness_timeout: self.liveness_timeout.clone(),
         }
     }
 }
 
-impl<T: Session> EventSinkEntry<T> {
-    pub fn new(rx: watch::Receiver<Option<EventSinkCtx<T>>>) -> Self {
-        EventSinkEntry { rx }
+impl<T: Session> Default for EventSinkEntry<T> {
+    fn default() -> Self {
+        let (tx, rx) = watch::channel(SinkSignal::Pending);
+        EventSinkEntry {
+            tx,
+            rx,
+            backlog: Arc::new(AsyncMutex::new(VecDeque::new())),
+            last_seen: Arc::new(StdMutex::new(Instant::now())),
+            liveness_timeout: Arc::new(StdMutex::new(None)),
+        }
     }
+}
 
+impl<T: Session> EventSinkEntry<T> {
     pub async fn get(&self) -> anyhow::Result<EventSinkCtx<T>> {
         let mut rx = self.rx.clone();
         loop {
+            if let Some(ctx) = self.backlog.lock().await.pop_front() {
+                return Ok(ctx);
+            }
             match rx.recv().await {
-                Some(Some(ctx)) => break Ok(ctx),
-                Some(None) => continue,
-                None => break Err(anyhow!("Session closed")),
+                Some(SinkSignal::Event(ctx)) => break Ok(ctx),
+                Some(SinkSignal::Pending) => continue,
+                Some(SinkSignal::Ended(reason)) => break Err(anyhow::Error::new(reason)),
+                None => break Err(anyhow::Error::new(SessionEnded::Closed)),
             }
         }
     }
 
     pub async fn try_get(&self) -> Option<EventSinkCtx<T>> {
+        if let Some(ctx) = self.backlog.lock().await.pop_front() {
+            return Some(ctx);
+        }
         let mut rx = self.rx.clone();
-        rx.recv().await.flatten()
+        match rx.recv().await {
+            Some(SinkSignal::Event(ctx)) => Some(ctx),
+            _ => None,
+        }
+    }
+
+    /// Queue `events` ahead of anything arriving live on this entry, and
+    /// wake any caller already blocked in [`get`](Self::get) so it notices
+    /// them. Used to replay a [`super::store::SessionStore`] backlog
+    /// without losing events to the single-slot live channel.
+    async fn enqueue_backlog(&self, events: impl IntoIterator<Item = EventSinkCtx<T>>) {
+        self.backlog.lock().await.extend(events);
+        // The value itself doesn't matter here, only that `send` bumps the
+        // channel's version so a waiting `rx.recv()` wakes up and re-checks
+        // the backlog.
+        let _ = self.tx.send(SinkSignal::Pending);
+    }
+
+    /// True unless a `liveness_timeout` was configured for this sink (see
+    /// [`register_sink_with_liveness`]) and no event has been produced for
+    /// longer than that timeout.
+    pub fn is_alive(&self) -> bool {
+        match *self.liveness_timeout.lock().unwrap() {
+            None => true,
+            Some(timeout) => self.last_seen.lock().unwrap().elapsed() < timeout,
+        }
+    }
+
+    fn touch(&self) {
+        *self.last_seen.lock().unwrap() = Instant::now();
+    }
+
+    /// Push `ctx` directly to every receiver currently watching this entry,
+    /// and record this as activity for the purposes of [`is_alive`].
+    /// Returns an error if the channel has no receivers left at all (the
+    /// entry's own stored `rx` keeps at least one alive, so in practice
+    /// this only fails once the entry itself has been dropped).
+    fn send(&self, ctx: EventSinkCtx<T>) -> anyhow::Result<()> {
+        self.touch();
+        self.tx
+            .send(SinkSignal::Event(ctx))
+            .map_err(|_| anyhow!("Session closed"))
+    }
+
+    /// Mark this entry as cleanly closed; existing `get()` callers see
+    /// `Err(SessionEnded::Closed)` instead of hanging forever.
+    pub fn close(&self) {
+        let _ = self.tx.send(SinkSignal::Ended(SessionEnded::Closed));
+    }
+
+    /// Mark this entry as evicted by the liveness sweeper; existing `get()`
+    /// callers see `Err(SessionEnded::Evicted)`, distinguishing a timeout
+    /// from a clean [`close`].
+    pub(super) fn evict(&self) {
+        let _ = self.tx.send(SinkSignal::Ended(SessionEnded::Evicted));
+    }
+}
+
+/// Identifies one of potentially several concurrent connections (devices)
+/// a single [`Session::Key`] may have live at once.
+pub type DeviceId = String;
+
+/// The set of sinks currently live for a single [`Session::Key`], one per
+/// connected device.
+struct SinkGroup<T: Session> {
+    devices: HashMap<DeviceId, EventSinkEntry<T>>,
+    most_recent: Option<DeviceId>,
+}
+
+impl<T: Session> Default for SinkGroup<T> {
+    fn default() -> Self {
+        SinkGroup {
+            devices: HashMap::new(),
+            most_recent: None,
+        }
     }
 }
 
-pub type EventSinkMap<T> = RwLock<HashMap<<T as Session>::Key, EventSinkEntry<T>>>;
+pub type EventSinkMap<T> = RwLock<HashMap<<T as Session>::Key, SinkGroup<T>>>;
+
+/// The (key, entry) pairs a new [`super::handler::register_handler`] call
+/// should spawn drivers for: one per currently-registered device.
+pub(super) async fn existing_entries<T: Session>(
+    map: &EventSinkMap<T>,
+) -> Vec<(T::Key, EventSinkEntry<T>)> {
+    map.read()
+        .await
+        .iter()
+        .flat_map(|(key, group)| {
+            group
+                .devices
+                .values()
+                .map(|entry| (key.clone(), entry.clone()))
+        })
+        .collect()
+}
 
 async fn try_get_event_sink_map<T: Session>(
     map: &'static SessionKindMap,
@@ -91,3 +252,322 @@ pub async fn get_or_create_event_sink_map<T: Session>() -> Arc<EventSinkMap<T>>
         .await
         .unwrap()
 }
+
+/// Register a device-less sink for `key`, as a convenience for callers that
+/// don't model multiple concurrent connections per key. Internally this
+/// just registers an anonymous device, so it composes with
+/// [`register_device`]/[`drop_device`]/[`broadcast`] — it becomes, and
+/// remains, the "most-recently-registered device" until something else
+/// registers after it.
+///
+/// Replays any events that were persisted by [`publish_event`] while `key`
+/// had no device registered at all.
+pub async fn register_sink<T: Session>(key: T::Key) -> anyhow::Result<EventSinkEntry<T>> {
+    register_device_impl::<T>(key, anonymous_device_id(), None).await
+}
+
+/// Like [`register_sink`], but also arms liveness tracking: if no event is
+/// produced for this device within `liveness_timeout`, a background
+/// sweeper (started lazily on the first such call for `T`, ticking every
+/// `heartbeat_interval` given to that first call; later calls' intervals
+/// are ignored) evicts it and existing `get()` callers see
+/// `Err(SessionEnded::Evicted)`.
+pub async fn register_sink_with_liveness<T: Session>(
+    key: T::Key,
+    heartbeat_interval: Duration,
+    liveness_timeout: Duration,
+) -> anyhow::Result<EventSinkEntry<T>> {
+    ensure_liveness_sweeper::<T>(heartbeat_interval).await;
+    register_device_impl::<T>(key, anonymous_device_id(), Some(liveness_timeout)).await
+}
+
+/// Register a sink for one specific `device_id` under `key`, leaving any
+/// other devices already registered for `key` untouched. Becomes the
+/// "most-recently-registered device" for `key`.
+pub async fn register_device<T: Session>(
+    key: T::Key,
+    device_id: impl Into<DeviceId>,
+) -> anyhow::Result<EventSinkEntry<T>> {
+    register_device_impl::<T>(key, device_id.into(), None).await
+}
+
+/// Drop `device_id`'s sink for `key`, closing it so in-flight `get()` calls
+/// return `Err(SessionEnded::Closed)`. If it was the most-recently
+/// registered device, another live device (if any) takes its place.
+pub async fn drop_device<T: Session>(key: &T::Key, device_id: &str) {
+    let map = get_or_create_event_sink_map::<T>().await;
+    let mut locked = map.write().await;
+    if let Some(group) = locked.get_mut(key) {
+        if let Some(entry) = group.devices.remove(device_id) {
+            entry.close();
+        }
+        if group.most_recent.as_deref() == Some(device_id) {
+            group.most_recent = group.devices.keys().next().cloned();
+        }
+        if group.devices.is_empty() {
+            locked.remove(key);
+        }
+    }
+}
+
+static ANON_DEVICE_COUNTER: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+
+fn anonymous_device_id() -> DeviceId {
+    format!(
+        "anon-{}",
+        ANON_DEVICE_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed)
+    )
+}
+
+async fn register_device_impl<T: Session>(
+    key: T::Key,
+    device_id: DeviceId,
+    liveness_timeout: Option<Duration>,
+) -> anyhow::Result<EventSinkEntry<T>> {
+    let map = get_or_create_event_sink_map::<T>().await;
+    let store = get_or_create_session_store::<T>().await;
+
+    let (entry, is_new_device) = {
+        // Held for the entire registration, including the store drain
+        // below: a concurrent broadcast/publish_event has to take this
+        // same lock to find the entry, so it can't slip a live event past
+        // the replayed backlog while we're still loading it in.
+        let mut locked = map.write().await;
+        let group = locked.entry(key.clone()).or_default();
+        let (entry, is_new_device) = match group.devices.entry(device_id.clone()) {
+            Entry::Occupied(occupied) => (occupied.get().clone(), false),
+            Entry::Vacant(vacant) => {
+                let entry = EventSinkEntry::default();
+                vacant.insert(entry.clone());
+                (entry, true)
+            }
+        };
+        group.most_recent = Some(device_id);
+
+        let pending = store.drain(&key).await?;
+        if let Some(&(last_seq, _)) = pending.last() {
+            // Queue the whole backlog on the entry itself rather than
+            // fanning it through `entry.send`, which only keeps its latest
+            // value — a backlog of more than one event would silently
+            // lose all but the last. Once it's queued here delivery is
+            // guaranteed, so it's safe to ack the whole backlog
+            // immediately.
+            entry
+                .enqueue_backlog(pending.into_iter().map(|(_, ctx)| ctx))
+                .await;
+            store.ack(&key, last_seq).await?;
+        }
+
+        (entry, is_new_device)
+    };
+
+    if liveness_timeout.is_some() {
+        *entry.liveness_timeout.lock().unwrap() = liveness_timeout;
+    }
+    entry.touch();
+
+    // Spawn a driver per device, not just for the first device of a key:
+    // otherwise a handler's only driver stops for good the moment that
+    // specific device is dropped, even if other devices for the same key
+    // are still live.
+    if is_new_device {
+        notify_handlers_of_new_entry(key.clone(), entry.clone()).await;
+    }
+
+    Ok(entry)
+}
+
+static LIVENESS_SWEEPER_MAP: Lazy<SessionKindMap> = Lazy::new(Default::default);
+
+/// Spawn the liveness sweeper for `T` the first time it's needed; a no-op
+/// on every subsequent call for the same `T`.
+async fn ensure_liveness_sweeper<T: Session>(heartbeat_interval: Duration) {
+    {
+        let locked = LIVENESS_SWEEPER_MAP.read().await;
+        if locked.contains_key(&TypeId::of::<T>()) {
+            return;
+        }
+    }
+
+    let mut locked = LIVENESS_SWEEPER_MAP.write().await;
+    if let Entry::Vacant(vacant) = locked.entry(TypeId::of::<T>()) {
+        vacant.insert(Arc::new(()) as Arc<dyn Any + Send + Sync>);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(heartbeat_interval);
+            loop {
+                ticker.tick().await;
+                sweep_dead_entries::<T>().await;
+            }
+        });
+    }
+}
+
+async fn sweep_dead_entries<T: Session>() {
+    let map = get_or_create_event_sink_map::<T>().await;
+    let mut locked = map.write().await;
+    locked.retain(|_, group| {
+        group.devices.retain(|_, entry| {
+            if entry.is_alive() {
+                true
+            } else {
+                entry.evict();
+                false
+            }
+        });
+        if group.devices.is_empty() {
+            return false;
+        }
+        let most_recent_still_live = group
+            .most_recent
+            .as_ref()
+            .map(|device_id| group.devices.contains_key(device_id))
+            .unwrap_or(false);
+        if !most_recent_still_live {
+            group.most_recent = group.devices.keys().next().cloned();
+        }
+        true
+    });
+}
+
+/// Fan `ctx` out to every device currently registered for `key`, or persist
+/// it to the session's [`super::store::SessionStore`] if none are
+/// registered yet so it can be replayed the next time a device registers
+/// for `key`.
+pub async fn broadcast<T: Session>(key: &T::Key, ctx: EventSinkCtx<T>) -> anyhow::Result<()> {
+    let map = get_or_create_event_sink_map::<T>().await;
+    let devices: Vec<EventSinkEntry<T>> = {
+        let locked = map.read().await;
+        locked
+            .get(key)
+            .map(|group| group.devices.values().cloned().collect())
+            .unwrap_or_default()
+    };
+
+    if devices.is_empty() {
+        let store = get_or_create_session_store::<T>().await;
+        store.enqueue(key, ctx).await?;
+        return Ok(());
+    }
+
+    for entry in devices {
+        // Best-effort per device: one device's channel being gone doesn't
+        // stop delivery to the others.
+        let _ = entry.send(ctx.clone());
+    }
+    Ok(())
+}
+
+/// Deliver `ctx` to `key`'s most-recently-registered device only, or
+/// persist it to the session's [`super::store::SessionStore`] if none is
+/// registered yet, same as [`broadcast`]. Unlike `broadcast`, this never
+/// duplicates `ctx` across multiple devices — for callers that don't think
+/// in terms of devices and want single-recipient delivery semantics.
+pub async fn publish_event<T: Session>(key: T::Key, ctx: EventSinkCtx<T>) -> anyhow::Result<()> {
+    let map = get_or_create_event_sink_map::<T>().await;
+    let entry = {
+        let locked = map.read().await;
+        locked.get(&key).and_then(|group| {
+            group
+                .most_recent
+                .as_ref()
+                .and_then(|device_id| group.devices.get(device_id))
+                .cloned()
+        })
+    };
+
+    let Some(entry) = entry else {
+        let store = get_or_create_session_store::<T>().await;
+        store.enqueue(&key, ctx).await?;
+        return Ok(());
+    };
+
+    entry.send(ctx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct BacklogReplaySession;
+
+    impl Session for BacklogReplaySession {
+        type Key = String;
+        type Event = u64;
+    }
+
+    #[tokio::test]
+    async fn backlog_replays_every_queued_event_in_order() {
+        let key = "backlog-replay-key".to_string();
+
+        for seq in 0..3u64 {
+            publish_event::<BacklogReplaySession>(
+                key.clone(),
+                EventSinkCtx::new(seq).unwrap().with_seq(seq),
+            )
+            .await
+            .unwrap();
+        }
+
+        let entry = register_sink::<BacklogReplaySession>(key).await.unwrap();
+
+        for seq in 0..3u64 {
+            let ctx = entry.get().await.unwrap();
+            assert_eq!(*ctx.deserialize().unwrap(), seq);
+        }
+    }
+
+    struct BroadcastTestSession;
+
+    impl Session for BroadcastTestSession {
+        type Key = String;
+        type Event = u64;
+    }
+
+    #[tokio::test]
+    async fn publish_event_targets_only_the_most_recent_device() {
+        let key = "broadcast-test-key".to_string();
+        let a = register_device::<BroadcastTestSession>(key.clone(), "device-a")
+            .await
+            .unwrap();
+        let b = register_device::<BroadcastTestSession>(key.clone(), "device-b")
+            .await
+            .unwrap();
+
+        broadcast::<BroadcastTestSession>(&key, EventSinkCtx::new(1).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(*a.get().await.unwrap().deserialize().unwrap(), 1);
+        assert_eq!(*b.get().await.unwrap().deserialize().unwrap(), 1);
+
+        publish_event::<BroadcastTestSession>(key.clone(), EventSinkCtx::new(2).unwrap())
+            .await
+            .unwrap();
+
+        // device-b registered most recently, so it alone receives this one.
+        assert_eq!(*b.get().await.unwrap().deserialize().unwrap(), 2);
+        assert!(tokio::time::timeout(Duration::from_millis(20), a.get())
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn eviction_sweep_marks_entry_dead_without_closing_it() {
+        let key = "liveness-test-key".to_string();
+        let entry = register_sink_with_liveness::<BroadcastTestSession>(
+            key,
+            Duration::from_millis(5),
+            Duration::from_millis(5),
+        )
+        .await
+        .unwrap();
+
+        assert!(entry.is_alive());
+
+        let err = entry.get().await.unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<SessionEnded>(),
+            Some(SessionEnded::Evicted)
+        ));
+        assert!(!entry.is_alive());
+    }
+}
@@ -0,0 +1,33 @@
+mod events;
+mod handler;
+mod map;
+mod reorder;
+mod store;
+
+pub use events::EventSinkCtx;
+pub use handler::{register_handler, EventHandler};
+pub use map::{
+    broadcast, drop_device, get_event_sink_map, get_or_create_event_sink_map, publish_event,
+    register_device, register_sink, register_sink_with_liveness, DeviceId, EventSinkEntry,
+    EventSinkMap, SessionEnded, SessionKindMap,
+};
+pub use reorder::{Delivery, Gap, OrderedSink};
+pub use store::{register_session_store, InMemorySessionStore, SessionStore};
+
+#[cfg(feature = "sled")]
+pub use store::SledSessionStore;
+
+use std::hash::Hash;
+
+/// A kind of session that can have events routed to it through an
+/// [`EventSinkMap`].
+///
+/// Each implementor identifies a distinct session flavor (e.g. a chat room,
+/// a device pairing) and is used purely as a type tag to key the
+/// [`SessionKindMap`] registry; it carries no state of its own.
+pub trait Session: 'static + Sync + Send {
+    /// Identifies a single instance of this session kind.
+    type Key: Eq + Hash + Clone + Send + Sync + 'static;
+    /// The event payload delivered to sinks for this session kind.
+    type Event: Clone + Send + Sync + 'static;
+}
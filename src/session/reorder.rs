@@ -0,0 +1,233 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::time::Duration;
+
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+use super::events::EventSinkCtx;
+use super::map::EventSinkEntry;
+use super::Session;
+
+/// Raised when a buffered sequence number never arrived and
+/// [`ReorderBuffer`] gave up waiting for it.
+#[derive(Debug, Clone, Copy)]
+pub struct Gap {
+    /// The sequence number that was never delivered.
+    pub expected: u64,
+    /// The sequence number reassembly resumed from.
+    pub resumed_at: u64,
+}
+
+/// One item handed back by [`OrderedSink::get`]: either the next event in
+/// sequence order, or notice that a gap was skipped to make progress.
+pub enum Delivery<T: Session> {
+    Event(EventSinkCtx<T>),
+    Gap(Gap),
+}
+
+struct ReorderState<T: Session> {
+    next_expected: u64,
+    pending: BTreeMap<u64, EventSinkCtx<T>>,
+    last_progress: Instant,
+}
+
+/// Reassembles a stream of [`EventSinkCtx::seq`]-tagged events into order,
+/// tolerating out-of-order arrival up to `window` buffered entries and
+/// `flush_timeout` of no progress before skipping a missing sequence
+/// number rather than stalling forever.
+struct ReorderBuffer<T: Session> {
+    window: usize,
+    flush_timeout: Duration,
+    state: Mutex<ReorderState<T>>,
+}
+
+impl<T: Session> ReorderBuffer<T> {
+    fn new(window: usize, flush_timeout: Duration) -> Self {
+        ReorderBuffer {
+            window,
+            flush_timeout,
+            state: Mutex::new(ReorderState {
+                next_expected: 0,
+                pending: BTreeMap::new(),
+                last_progress: Instant::now(),
+            }),
+        }
+    }
+
+    async fn admit(&self, ctx: EventSinkCtx<T>) -> Vec<Delivery<T>> {
+        let mut state = self.state.lock().await;
+
+        // A late or duplicate arrival of a sequence number already
+        // delivered (or already skipped past) would otherwise become the
+        // new lowest pending key and drag `next_expected` backward the
+        // next time the window overflows. It's stale, not out of order —
+        // drop it.
+        if ctx.seq() < state.next_expected {
+            return Vec::new();
+        }
+
+        state.pending.insert(ctx.seq(), ctx);
+
+        let released = drain_contiguous(&mut state);
+        if !released.is_empty() {
+            return released.into_iter().map(Delivery::Event).collect();
+        }
+
+        if state.pending.len() > self.window {
+            return force_skip(&mut state);
+        }
+
+        Vec::new()
+    }
+
+    async fn poll_timeout(&self) -> Vec<Delivery<T>> {
+        let mut state = self.state.lock().await;
+        if state.pending.is_empty() || state.last_progress.elapsed() < self.flush_timeout {
+            return Vec::new();
+        }
+        force_skip(&mut state)
+    }
+
+    /// The instant `flush_timeout` will next have elapsed since the last
+    /// contiguous release, i.e. when [`poll_timeout`](Self::poll_timeout)
+    /// should next be given a chance to force a skip. Anchored to
+    /// `last_progress` rather than "now" so admitting an event that
+    /// doesn't itself make progress can't keep pushing the deadline out.
+    async fn deadline(&self) -> Instant {
+        self.state.lock().await.last_progress + self.flush_timeout
+    }
+}
+
+fn drain_contiguous<T: Session>(state: &mut ReorderState<T>) -> Vec<EventSinkCtx<T>> {
+    let mut released = Vec::new();
+    while let Some(ctx) = state.pending.remove(&state.next_expected) {
+        state.next_expected += 1;
+        released.push(ctx);
+    }
+    if !released.is_empty() {
+        state.last_progress = Instant::now();
+    }
+    released
+}
+
+fn force_skip<T: Session>(state: &mut ReorderState<T>) -> Vec<Delivery<T>> {
+    let Some(&lowest) = state.pending.keys().next() else {
+        return Vec::new();
+    };
+
+    let gap = Gap {
+        expected: state.next_expected,
+        resumed_at: lowest,
+    };
+    state.next_expected = lowest;
+    state.last_progress = Instant::now();
+
+    let mut out = vec![Delivery::Gap(gap)];
+    out.extend(drain_contiguous(state).into_iter().map(Delivery::Event));
+    out
+}
+
+/// Wraps an [`EventSinkEntry`] so callers see events released in sequence
+/// order rather than arrival order.
+pub struct OrderedSink<T: Session> {
+    entry: EventSinkEntry<T>,
+    buffer: ReorderBuffer<T>,
+    ready: Mutex<VecDeque<Delivery<T>>>,
+}
+
+impl<T: Session> OrderedSink<T> {
+    pub fn new(entry: EventSinkEntry<T>, window: usize, flush_timeout: Duration) -> Self {
+        OrderedSink {
+            entry,
+            buffer: ReorderBuffer::new(window, flush_timeout),
+            ready: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Return the next event in sequence order, or a [`Delivery::Gap`] if
+    /// reassembly had to skip a missing sequence number to make progress.
+    pub async fn get(&self) -> anyhow::Result<Delivery<T>> {
+        loop {
+            if let Some(delivery) = self.ready.lock().await.pop_front() {
+                return Ok(delivery);
+            }
+
+            let deadline = self.buffer.deadline().await;
+            let mut released = tokio::select! {
+                raw = self.entry.get() => self.buffer.admit(raw?).await,
+                _ = tokio::time::sleep_until(deadline) => self.buffer.poll_timeout().await,
+            };
+
+            if released.is_empty() {
+                continue;
+            }
+            let first = released.remove(0);
+            self.ready.lock().await.extend(released);
+            return Ok(first);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct ReorderTestSession;
+
+    impl Session for ReorderTestSession {
+        type Key = String;
+        type Event = u64;
+    }
+
+    fn ctx(seq: u64) -> EventSinkCtx<ReorderTestSession> {
+        EventSinkCtx::new(seq).unwrap().with_seq(seq)
+    }
+
+    #[tokio::test]
+    async fn in_order_events_release_immediately() {
+        let buffer = ReorderBuffer::<ReorderTestSession>::new(4, Duration::from_secs(60));
+
+        let released = buffer.admit(ctx(0)).await;
+        assert_eq!(released.len(), 1);
+        assert!(matches!(&released[0], Delivery::Event(e) if e.seq() == 0));
+    }
+
+    #[tokio::test]
+    async fn missing_sequence_is_skipped_once_window_is_exceeded() {
+        let buffer = ReorderBuffer::<ReorderTestSession>::new(1, Duration::from_secs(60));
+
+        // seq 0 never arrives; 1 and 2 arrive instead, overflowing a
+        // window of 1 and forcing a skip past the gap.
+        let first = buffer.admit(ctx(1)).await;
+        assert!(first.is_empty());
+
+        let released = buffer.admit(ctx(2)).await;
+        assert_eq!(released.len(), 3);
+        assert!(matches!(
+            released[0],
+            Delivery::Gap(Gap {
+                expected: 0,
+                resumed_at: 1,
+            })
+        ));
+        assert!(matches!(&released[1], Delivery::Event(e) if e.seq() == 1));
+        assert!(matches!(&released[2], Delivery::Event(e) if e.seq() == 2));
+    }
+
+    #[tokio::test]
+    async fn stale_sequence_does_not_rewind_progress() {
+        let buffer = ReorderBuffer::<ReorderTestSession>::new(4, Duration::from_secs(60));
+
+        let released = buffer.admit(ctx(0)).await;
+        assert_eq!(released.len(), 1);
+
+        // A duplicate/late arrival of an already-delivered sequence number
+        // must be dropped, not buffered as a new low watermark.
+        let late = buffer.admit(ctx(0)).await;
+        assert!(late.is_empty());
+
+        let released = buffer.admit(ctx(1)).await;
+        assert_eq!(released.len(), 1);
+        assert!(matches!(&released[0], Delivery::Event(e) if e.seq() == 1));
+    }
+}
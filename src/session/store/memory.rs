@@ -0,0 +1,55 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use async_trait::async_trait;
+use tokio::sync::RwLock;
+
+use super::SessionStore;
+use crate::session::events::EventSinkCtx;
+use crate::session::Session;
+
+/// The default, non-persistent [`SessionStore`]. Queued events are lost on
+/// process restart; enable the `sled` feature and register a
+/// [`super::SledSessionStore`] for durability across restarts.
+pub struct InMemorySessionStore<T: Session> {
+    next_seq: AtomicU64,
+    queues: RwLock<HashMap<T::Key, VecDeque<(u64, EventSinkCtx<T>)>>>,
+}
+
+impl<T: Session> Default for InMemorySessionStore<T> {
+    fn default() -> Self {
+        InMemorySessionStore {
+            next_seq: AtomicU64::new(0),
+            queues: RwLock::new(HashMap::new()),
+        }
+    }
+}
+
+#[async_trait]
+impl<T: Session> SessionStore<T> for InMemorySessionStore<T> {
+    async fn enqueue(&self, key: &T::Key, event: EventSinkCtx<T>) -> anyhow::Result<u64> {
+        let seq = self.next_seq.fetch_add(1, Ordering::Relaxed);
+        let mut locked = self.queues.write().await;
+        locked
+            .entry(key.clone())
+            .or_default()
+            .push_back((seq, event));
+        Ok(seq)
+    }
+
+    async fn drain(&self, key: &T::Key) -> anyhow::Result<Vec<(u64, EventSinkCtx<T>)>> {
+        let locked = self.queues.read().await;
+        Ok(locked
+            .get(key)
+            .map(|queue| queue.iter().cloned().collect())
+            .unwrap_or_default())
+    }
+
+    async fn ack(&self, key: &T::Key, seq: u64) -> anyhow::Result<()> {
+        let mut locked = self.queues.write().await;
+        if let Some(queue) = locked.get_mut(key) {
+            queue.retain(|(queued_seq, _)| *queued_seq > seq);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,82 @@
+mod memory;
+
+#[cfg(feature = "sled")]
+mod sled_store;
+
+pub use memory::InMemorySessionStore;
+
+#[cfg(feature = "sled")]
+pub use sled_store::SledSessionStore;
+
+use std::any::{Any, TypeId};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use once_cell::sync::Lazy;
+use tokio::sync::RwLock;
+
+use super::events::EventSinkCtx;
+use super::map::SessionKindMap;
+use super::Session;
+
+/// A persistent store-and-forward queue for events that arrive while no
+/// live sink is registered for a [`Session::Key`].
+///
+/// Implementors must preserve enqueue order per key: `drain` returns events
+/// oldest-first, and `ack` lets the caller trim everything up to and
+/// including a delivered sequence number once it has been handed off
+/// successfully.
+#[async_trait]
+pub trait SessionStore<T: Session>: Send + Sync + 'static {
+    /// Persist `event` for later delivery to `key`. Returns the sequence
+    /// number assigned to the event.
+    async fn enqueue(&self, key: &T::Key, event: EventSinkCtx<T>) -> anyhow::Result<u64>;
+
+    /// Return every event queued for `key`, oldest first, without removing
+    /// them. Callers should `ack` once delivery is confirmed.
+    async fn drain(&self, key: &T::Key) -> anyhow::Result<Vec<(u64, EventSinkCtx<T>)>>;
+
+    /// Drop every queued event for `key` up to and including `seq`.
+    async fn ack(&self, key: &T::Key, seq: u64) -> anyhow::Result<()>;
+}
+
+static SESSION_STORE_MAP: Lazy<SessionKindMap> = Lazy::new(Default::default);
+
+/// Register the [`SessionStore`] used for session kind `T`. Replaces any
+/// store previously registered for `T`.
+pub async fn register_session_store<T: Session>(store: Arc<dyn SessionStore<T>>) {
+    let mut locked = SESSION_STORE_MAP.write().await;
+    locked.insert(
+        TypeId::of::<T>(),
+        Arc::new(store) as Arc<dyn Any + Send + Sync>,
+    );
+}
+
+/// Fetch the [`SessionStore`] registered for `T`, falling back to a fresh
+/// [`InMemorySessionStore`] if none has been registered yet.
+pub(super) async fn get_or_create_session_store<T: Session>() -> Arc<dyn SessionStore<T>> {
+    {
+        let locked = SESSION_STORE_MAP.read().await;
+        if let Some(entry) = locked.get(&TypeId::of::<T>()) {
+            return entry
+                .clone()
+                .downcast::<Arc<dyn SessionStore<T>>>()
+                .unwrap()
+                .as_ref()
+                .clone();
+        }
+    }
+
+    let mut locked = SESSION_STORE_MAP.write().await;
+    locked
+        .entry(TypeId::of::<T>())
+        .or_insert_with(|| {
+            let default: Arc<dyn SessionStore<T>> = Arc::new(InMemorySessionStore::default());
+            Arc::new(default) as Arc<dyn Any + Send + Sync>
+        })
+        .clone()
+        .downcast::<Arc<dyn SessionStore<T>>>()
+        .unwrap()
+        .as_ref()
+        .clone()
+}
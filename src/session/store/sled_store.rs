@@ -0,0 +1,107 @@
+use std::marker::PhantomData;
+
+use async_trait::async_trait;
+use serde::{de::DeserializeOwned, Serialize};
+
+use super::SessionStore;
+use crate::session::events::EventSinkCtx;
+use crate::session::Session;
+
+/// A [`SessionStore`] backed by a [`sled`] tree, so queued events survive a
+/// process restart. Keys must round-trip through `serde_json`; events are
+/// persisted as [`EventSinkCtx::raw`] bytes directly, with no decode/encode
+/// through `T::Event`, so fields it doesn't model survive a restart too.
+/// Within a tree, entries are stored under the JSON-encoded key, a NUL byte,
+/// then `{seq:020}`, so iteration order matches enqueue order.
+pub struct SledSessionStore<T: Session> {
+    tree: sled::Tree,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T: Session> SledSessionStore<T>
+where
+    T::Key: Serialize + DeserializeOwned,
+{
+    pub fn new(db: &sled::Db, tree_name: &str) -> anyhow::Result<Self> {
+        Ok(SledSessionStore {
+            tree: db.open_tree(tree_name)?,
+            _marker: PhantomData,
+        })
+    }
+
+    fn entry_key(key: &T::Key, seq: u64) -> anyhow::Result<Vec<u8>> {
+        let mut prefix = serde_json::to_vec(key)?;
+        prefix.push(0);
+        prefix.extend_from_slice(format!("{seq:020}").as_bytes());
+        Ok(prefix)
+    }
+
+    fn key_prefix(key: &T::Key) -> anyhow::Result<Vec<u8>> {
+        let mut prefix = serde_json::to_vec(key)?;
+        prefix.push(0);
+        Ok(prefix)
+    }
+}
+
+/// Encode `(event_seq, raw)` as an 8-byte big-endian sequence number
+/// followed by the raw event bytes verbatim, so persisting never touches
+/// `T::Event`.
+fn encode_value(event_seq: u64, raw: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(8 + raw.len());
+    out.extend_from_slice(&event_seq.to_be_bytes());
+    out.extend_from_slice(raw);
+    out
+}
+
+fn decode_value(value: &[u8]) -> anyhow::Result<(u64, &[u8])> {
+    if value.len() < 8 {
+        anyhow::bail!("truncated session store entry");
+    }
+    let (seq_bytes, raw) = value.split_at(8);
+    let event_seq = u64::from_be_bytes(seq_bytes.try_into().unwrap());
+    Ok((event_seq, raw))
+}
+
+#[async_trait]
+impl<T: Session> SessionStore<T> for SledSessionStore<T>
+where
+    T::Key: Serialize + DeserializeOwned,
+{
+    async fn enqueue(&self, key: &T::Key, event: EventSinkCtx<T>) -> anyhow::Result<u64> {
+        let seq = self.tree.generate_id()?;
+        let entry_key = Self::entry_key(key, seq)?;
+        // Persist the producer's sequence tag and the event's raw bytes
+        // untouched so `OrderedSink` can reassemble later without a
+        // decode/encode round trip through `T::Event`.
+        let value = encode_value(event.seq(), event.raw());
+        self.tree.insert(entry_key, value)?;
+        Ok(seq)
+    }
+
+    async fn drain(&self, key: &T::Key) -> anyhow::Result<Vec<(u64, EventSinkCtx<T>)>> {
+        let prefix = Self::key_prefix(key)?;
+        let mut out = Vec::new();
+        for kv in self.tree.scan_prefix(&prefix) {
+            let (db_key, value) = kv?;
+            let seq: u64 = std::str::from_utf8(&db_key[prefix.len()..])?.parse()?;
+            let (event_seq, raw) = decode_value(&value)?;
+            out.push((
+                seq,
+                EventSinkCtx::from_raw(raw.to_vec()).with_seq(event_seq),
+            ));
+        }
+        Ok(out)
+    }
+
+    async fn ack(&self, key: &T::Key, seq: u64) -> anyhow::Result<()> {
+        let prefix = Self::key_prefix(key)?;
+        for kv in self.tree.scan_prefix(&prefix) {
+            let (db_key, _) = kv?;
+            let entry_seq: u64 = std::str::from_utf8(&db_key[prefix.len()..])?.parse()?;
+            if entry_seq <= seq {
+                self.tree.remove(db_key)?;
+            }
+        }
+        Ok(())
+    }
+}